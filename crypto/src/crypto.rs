@@ -1,8 +1,11 @@
 //! Collection of encryption and digital signature algorithms
 
 pub mod dsa;
+pub mod ec_elgamal;
 pub mod elgamal;
+pub mod keygen;
 pub mod rsa;
+pub mod secret;
 
 use crate::utils::random_bignum;
 use num_bigint::BigUint;
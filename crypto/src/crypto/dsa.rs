@@ -1,6 +1,17 @@
 //! Implementation of the DSA algorithm
 
 use super::*;
+use hmac::{Hmac, Mac};
+use num_traits::One;
+use secret::Secret;
+use sha2::Sha256;
+
+/// `HMAC-SHA256`, used by the RFC 6979 deterministic nonce derivation
+type HmacSha256 = Hmac<Sha256>;
+
+/// Bit length of the SHA-256 digest underlying [`DSA::hash`], i.e. `hlen` in
+/// RFC 6979
+const HASH_BITS: usize = 256;
 
 pub struct DSA {
     /// Public prime
@@ -11,8 +22,167 @@ pub struct DSA {
     pub g: BigUint,
     /// Public value _g_<sup>_x_</sup> mod _p_
     pub y: BigUint,
-    /// Secret exponent
-    pub x: BigUint,
+    /// Secret exponent, wiped from memory on drop
+    pub x: Secret,
+}
+
+impl DSA {
+    /// Generate a fresh DSA key with an `l`-bit prime `p` and an
+    /// `n`-bit prime order `q`
+    ///
+    /// Generates `q` first, then tries random (`l` - `n`)-bit multipliers `k`
+    /// until `p` = `k` * `q` + 1 is itself prime, then finds a generator `g`
+    /// of the order-`q` subgroup of _Z_<sub>_p_</sub><sup>*</sup> by raising
+    /// a random base to the power (_p_ - 1) / _q_ until the result is not 1,
+    /// and finally picks a random secret exponent `x` in `[1, q - 1]`.
+    pub fn generate(l: usize, n: usize) -> Self {
+        let q = keygen::gen_prime(n);
+
+        let p = loop {
+            let k = random_bignum(&(BigUint::one() << (l - n - 1)), &(BigUint::one() << (l - n)));
+            let candidate = &k * &q + BigUint::one();
+
+            if keygen::is_prime(&candidate, keygen::MR_ROUNDS) {
+                break candidate;
+            }
+        };
+
+        let exponent = (&p - BigUint::one()) / &q;
+        let g = loop {
+            let h = random_bignum(&BigUint::from(2u32), &(&p - BigUint::from(2u32)));
+            let candidate = h.modpow(&exponent, &p);
+
+            if candidate != BigUint::one() {
+                break candidate;
+            }
+        };
+
+        let x = random_bignum(&BigUint::one(), &q);
+        let y = g.modpow(&x, &p);
+
+        DSA {
+            p,
+            q,
+            g,
+            y,
+            x: Secret::from(x),
+        }
+    }
+
+    /// Sign `m` with a nonce `k` derived deterministically from the message
+    /// hash and the private key, following RFC 6979, instead of a randomly
+    /// generated one
+    ///
+    /// This avoids leaking the private key `x` through a weak RNG or an
+    /// accidentally reused nonce, while producing a signature that verifies
+    /// exactly like [`Sign::sign`]'s.
+    pub fn sign_deterministic(&self, m: &BigUint) -> Signature {
+        let h = Self::hash(m);
+        let k = Secret::from(self.rfc6979_nonce(&h));
+        // By Fermat's little theorem, k^-1 mod q == k^(q-2) mod q
+        let k_inv = Secret::from(
+            k.expose_secret()
+                .modpow(&(&self.q - BigUint::from(2u32)), &self.q),
+        );
+
+        let r = self.g.modpow(&k.expose_secret(), &self.p) % &self.q;
+        let s = (k_inv.expose_secret() * (h + self.x.expose_secret() * &r)) % &self.q;
+
+        Signature::Pair(r, s)
+    }
+
+    /// Length in bytes of `q` (`rlen` in RFC 6979)
+    fn qlen_bytes(&self) -> usize {
+        (self.q.bits() as usize).div_ceil(8)
+    }
+
+    /// RFC 6979 `int2octets`: big-endian encode `v` into exactly
+    /// `qlen_bytes` bytes, truncating or zero-padding on the left as needed
+    fn int2octets(&self, v: &BigUint) -> Vec<u8> {
+        let len = self.qlen_bytes();
+        let bytes = v.to_bytes_be();
+
+        if bytes.len() < len {
+            let mut padded = vec![0u8; len - bytes.len()];
+            padded.extend_from_slice(&bytes);
+            padded
+        } else {
+            bytes[bytes.len() - len..].to_vec()
+        }
+    }
+
+    /// RFC 6979 `bits2int`: keep only the leftmost `qlen` bits of the
+    /// `hlen`-bit value `h`, right-shifting away any excess low-order bits
+    fn bits2int(&self, h: &BigUint, hlen_bits: usize) -> BigUint {
+        let qlen = self.q.bits() as usize;
+
+        if hlen_bits > qlen {
+            h >> (hlen_bits - qlen)
+        } else {
+            h.clone()
+        }
+    }
+
+    /// RFC 6979 `bits2octets`: apply [`DSA::bits2int`] to the `hlen`-bit
+    /// hash `h`, reduce the result mod `q`, then encode with
+    /// [`DSA::int2octets`]
+    fn bits2octets(&self, h: &BigUint, hlen_bits: usize) -> Vec<u8> {
+        self.int2octets(&(self.bits2int(h, hlen_bits) % &self.q))
+    }
+
+    /// RFC 6979 deterministic nonce derivation
+    ///
+    /// Starts from `V` = 0x01 repeated 32 times and `K` = 0x00 repeated 32
+    /// times, folds in the private key and the message hash over two
+    /// `HMAC_K(V || tag || int2octets(x) || bits2octets(h))` rounds (tagged
+    /// 0x00 then 0x01, each followed by `V = HMAC_K(V)`), then generates
+    /// candidates `T` by repeatedly extending `V = HMAC_K(V)` until there
+    /// are enough bits, applying [`DSA::bits2int`] to keep the leftmost
+    /// `qlen` bits of `T` (the result may still be `q` or more), and
+    /// retrying with `K = HMAC_K(V || 0x00)`,
+    /// `V = HMAC_K(V)` if the candidate falls outside `[1, q - 1]`.
+    fn rfc6979_nonce(&self, h: &BigUint) -> BigUint {
+        let hmac = |key: &[u8], chunks: &[&[u8]]| -> Vec<u8> {
+            let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+
+            for chunk in chunks {
+                mac.update(chunk);
+            }
+
+            mac.finalize().into_bytes().to_vec()
+        };
+
+        let x_octets = self.int2octets(&self.x.expose_secret());
+        let h_octets = self.bits2octets(h, HASH_BITS);
+
+        let mut v = vec![0x01u8; 32];
+        let mut k = vec![0x00u8; 32];
+
+        k = hmac(&k, &[&v, &[0x00], &x_octets, &h_octets]);
+        v = hmac(&k, &[&v]);
+
+        k = hmac(&k, &[&v, &[0x01], &x_octets, &h_octets]);
+        v = hmac(&k, &[&v]);
+
+        loop {
+            let mut t = Vec::new();
+
+            while t.len() < self.qlen_bytes() {
+                v = hmac(&k, &[&v]);
+                t.extend_from_slice(&v);
+            }
+
+            let t = &t[..self.qlen_bytes()];
+            let candidate = self.bits2int(&BigUint::from_bytes_be(t), t.len() * 8);
+
+            if candidate >= BigUint::one() && candidate < self.q {
+                return candidate;
+            }
+
+            k = hmac(&k, &[&v, &[0x00]]);
+            v = hmac(&k, &[&v]);
+        }
+    }
 }
 
 /// Use the DSA algorithm to sign messages and verify signatures
@@ -34,12 +204,15 @@ impl Sign for DSA {
     /// (where 0 < _k_ < _q_ is randomly generated)
     fn sign(&self, m: &BigUint) -> Signature {
         // Generate a random k between 1 and q-1
-        let k = random_bignum(&BigUint::from(1u32), &self.q);
+        let k = Secret::from(random_bignum(&BigUint::from(1u32), &self.q));
         // By Fermat's little theorem, k^-1 mod q == k^(q-2) mod q
-        let k_inv = k.modpow(&(&self.q - BigUint::from(2u32)), &self.q);
+        let k_inv = Secret::from(
+            k.expose_secret()
+                .modpow(&(&self.q - BigUint::from(2u32)), &self.q),
+        );
 
-        let r = self.g.modpow(&k, &self.p) % &self.q;
-        let s = (k_inv * (Self::hash(m) + &self.x * &r)) % &self.q;
+        let r = self.g.modpow(&k.expose_secret(), &self.p) % &self.q;
+        let s = (k_inv.expose_secret() * (Self::hash(m) + self.x.expose_secret() * &r)) % &self.q;
 
         Signature::Pair(r, s)
     }
@@ -70,6 +243,10 @@ impl Sign for DSA {
     }
 }
 
+/// Dropping a `DSA` key wipes its secret exponent `x` from memory, since it
+/// is a [`Secret`]
+impl zeroize::ZeroizeOnDrop for DSA {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,7 +260,7 @@ mod tests {
             q: BigUint::from(17389u32),
             g: BigUint::from(10083255u32),
             y: BigUint::from(119946265u32),
-            x: BigUint::from(12496u32),
+            x: Secret::from(BigUint::from(12496u32)),
         };
 
         let m = BigUint::from(124540019u32);
@@ -101,7 +278,7 @@ mod tests {
             q: bignum(b"77329472688943863782809684314309611412099174806793669931856388423150706393447"),
             g: bignum(b"9776352536598101331432253534187162046797971077269626288014571662233110493164650880686970679823027823782693979916732176955913010538621389580201197451835850449349401498615800410766032380854611786555966853817267325239512000930084764892283051664768149497273193030000400930558709969898151798576730413946075652301924503319572711955695693129578328117269721696248195211019503146843488075633287525582407824111700730431268482972630743457867358536579474264670693432213201731134512477024166751325249293155566771418339122598660995442996355382603925473715661262608962598234427508654831621526829645072886787047595607539558463000444"),
             y: bignum(b"10724392575130207156071095265641597303485892984432677155198160243874833612494142949396125374542186243165624569971691773432654567439240367909706445219719828407566860880184150096675886463333075597901899438944411768285518047404374844574637379248883947243547127317112321816149952128981946891128067470332020385770766406208444905612330088021679921652404504950961006753477156457704784219178166816133076168833926154370406480664919715566381132438484472750743002339128065378009284094527365506266826999182633125457061276960366686790260766865762855660374392088920250978820732228600679753002316993258157710832553602657970334455236"),
-            x: bignum(b"67280696483525608869730051502255070481129155564254771476611158635103194927878"),
+            x: Secret::from(bignum(b"67280696483525608869730051502255070481129155564254771476611158635103194927878")),
         };
 
         let m = bignum(b"1482726341215123");
@@ -112,6 +289,34 @@ mod tests {
         assert!(verified);
     }
 
+    #[test]
+    fn test_dsa_deterministic() {
+        let dsa = DSA {
+            p: BigUint::from(124540019u32),
+            q: BigUint::from(17389u32),
+            g: BigUint::from(10083255u32),
+            y: BigUint::from(119946265u32),
+            x: Secret::from(BigUint::from(12496u32)),
+        };
+
+        let m = BigUint::from(124540019u32);
+
+        let signed = dsa.sign_deterministic(&m);
+        assert!(dsa.verify(&m, &signed));
+
+        // Signing the same message twice must yield the same nonce, and
+        // hence the same signature
+        let signed_again = dsa.sign_deterministic(&m);
+
+        match (signed, signed_again) {
+            (Signature::Pair(r1, s1), Signature::Pair(r2, s2)) => {
+                assert_eq!(r1, r2);
+                assert_eq!(s1, s2);
+            }
+            _ => panic!("Not a pair"),
+        }
+    }
+
     #[test]
     fn test_dsa_hash() {
         assert_eq!(
@@ -0,0 +1,263 @@
+//! Elliptic-curve ElGamal over the BabyJubJub twisted Edwards curve
+//!
+//! This is a much smaller-key, modern alternative to the multiplicative-group
+//! [`super::elgamal`] already present, operating on points of the curve
+//! _a_ * _x_<sup>2</sup> + _y_<sup>2</sup> = 1 + _d_ * _x_<sup>2</sup> *
+//! _y_<sup>2</sup> over the prime field `Q`.
+
+use crate::utils::{bignum, mod_inverse, random_bignum};
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Prime order of the BabyJubJub base field
+fn modulus() -> BigUint {
+    bignum(b"21888242871839275222246405745257275088548364400416034343698204186575808495617")
+}
+
+/// Twisted Edwards curve parameter `a`
+fn param_a() -> BigUint {
+    BigUint::from(168700u32)
+}
+
+/// Twisted Edwards curve parameter `d`
+fn param_d() -> BigUint {
+    BigUint::from(168696u32)
+}
+
+fn add_mod(a: &BigUint, b: &BigUint, q: &BigUint) -> BigUint {
+    (a + b) % q
+}
+
+fn sub_mod(a: &BigUint, b: &BigUint, q: &BigUint) -> BigUint {
+    if a >= b {
+        (a - b) % q
+    } else {
+        (q + a - b) % q
+    }
+}
+
+fn mul_mod(a: &BigUint, b: &BigUint, q: &BigUint) -> BigUint {
+    (a * b) % q
+}
+
+/// A point on the BabyJubJub curve, in affine coordinates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Point {
+    pub x: BigUint,
+    pub y: BigUint,
+}
+
+impl Point {
+    /// The neutral element (0, 1) of the twisted Edwards group law
+    pub fn identity() -> Self {
+        Point {
+            x: BigUint::zero(),
+            y: BigUint::one(),
+        }
+    }
+
+    /// The standard BabyJubJub base point (often called `Base8`)
+    pub fn generator() -> Self {
+        Point {
+            x: bignum(
+                b"5299619240641551281634865583518297030282874472190772894086521144482721001553",
+            ),
+            y: bignum(
+                b"16950150798460657717958625567821834550301663161624707787222815936182638968203",
+            ),
+        }
+    }
+
+    /// Embed the affine point as (_X_ : _Y_ : 1) in homogeneous coordinates
+    fn to_projective(&self) -> (BigUint, BigUint, BigUint) {
+        (self.x.clone(), self.y.clone(), BigUint::one())
+    }
+
+    /// Recover the affine point from homogeneous coordinates (_X_ : _Y_ :
+    /// _Z_) by dividing through by _Z_
+    fn from_projective(x: BigUint, y: BigUint, z: BigUint) -> Self {
+        let q = modulus();
+        let z_inv = mod_inverse(&z, &q);
+
+        Point {
+            x: mul_mod(&x, &z_inv, &q),
+            y: mul_mod(&y, &z_inv, &q),
+        }
+    }
+
+    /// Add two points using the unified twisted Edwards addition law
+    ///
+    /// _x_<sub>3</sub> = (_x_<sub>1</sub>_y_<sub>2</sub> +
+    /// _y_<sub>1</sub>_x_<sub>2</sub>) / (1 +
+    /// _d_*_x_<sub>1</sub>_x_<sub>2</sub>_y_<sub>1</sub>_y_<sub>2</sub>)
+    ///
+    /// _y_<sub>3</sub> = (_y_<sub>1</sub>_y_<sub>2</sub> -
+    /// _a_*_x_<sub>1</sub>_x_<sub>2</sub>) / (1 -
+    /// _d_*_x_<sub>1</sub>_x_<sub>2</sub>_y_<sub>1</sub>_y_<sub>2</sub>)
+    ///
+    /// which also correctly doubles a point when added to itself
+    pub fn add(&self, other: &Point) -> Point {
+        let q = modulus();
+        let (a, d) = (param_a(), param_d());
+
+        let (x1, y1, _) = self.to_projective();
+        let (x2, y2, _) = other.to_projective();
+
+        let x1y2 = mul_mod(&x1, &y2, &q);
+        let y1x2 = mul_mod(&y1, &x2, &q);
+        let y1y2 = mul_mod(&y1, &y2, &q);
+        let x1x2 = mul_mod(&x1, &x2, &q);
+
+        let cross = mul_mod(&mul_mod(&d, &x1x2, &q), &y1y2, &q);
+
+        let x3_num = add_mod(&x1y2, &y1x2, &q);
+        let x3_den = add_mod(&BigUint::one(), &cross, &q);
+
+        let y3_num = sub_mod(&y1y2, &mul_mod(&a, &x1x2, &q), &q);
+        let y3_den = sub_mod(&BigUint::one(), &cross, &q);
+
+        Point::from_projective(
+            mul_mod(&x3_num, &mod_inverse(&x3_den, &q), &q),
+            mul_mod(&y3_num, &mod_inverse(&y3_den, &q), &q),
+            BigUint::one(),
+        )
+    }
+
+    /// Negate a point; on a twisted Edwards curve this simply flips the
+    /// _x_-coordinate
+    pub fn neg(&self) -> Point {
+        let q = modulus();
+
+        Point {
+            x: sub_mod(&q, &self.x, &q),
+            y: self.y.clone(),
+        }
+    }
+
+    /// Scalar multiplication via double-and-add
+    pub fn scalar_mul(&self, k: &BigUint) -> Point {
+        let mut result = Point::identity();
+        let mut addend = self.clone();
+        let mut k = k.clone();
+
+        while k > BigUint::zero() {
+            if k.is_odd() {
+                result = result.add(&addend);
+            }
+
+            addend = addend.add(&addend);
+            k >>= 1;
+        }
+
+        result
+    }
+}
+
+/// Pair of points (_c_<sub>1</sub>, _c_<sub>2</sub>), the point-valued
+/// analogue of [`super::Ciphertext`] for the curve-based algorithms in this
+/// module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointCiphertext {
+    pub c1: Point,
+    pub c2: Point,
+}
+
+/// Add encrypt/decrypt capabilities to algorithms operating on curve points,
+/// mirroring [`super::Encrypt`] for message and ciphertext types that are
+/// [`Point`]s rather than `BigUint`s
+pub trait EncryptPoint {
+    fn encrypt(&self, m: &Point) -> PointCiphertext;
+    fn decrypt(&self, c: &PointCiphertext) -> Point;
+}
+
+/// Use elliptic-curve ElGamal to encrypt and decrypt message points
+pub struct ECElGamal {
+    /// Public key _Y_ = _x_ * _G_
+    pub y: Point,
+    /// Secret scalar
+    pub x: BigUint,
+}
+
+impl ECElGamal {
+    /// Generate a fresh key pair by picking a random secret scalar in
+    /// `[1, Q - 1]` and deriving the public key _Y_ = _x_ * _G_
+    pub fn generate() -> Self {
+        let q = modulus();
+        let x = random_bignum(&BigUint::one(), &q);
+        let y = Point::generator().scalar_mul(&x);
+
+        ECElGamal { x, y }
+    }
+}
+
+/// Use elliptic-curve ElGamal to encrypt and decrypt message points
+impl EncryptPoint for ECElGamal {
+    /// Compute (_c_<sub>1</sub>, _c_<sub>2</sub>) with
+    ///
+    /// _c_<sub>1</sub> = _k_ * _G_
+    ///
+    /// _c_<sub>2</sub> = _`m`_ + _k_ * _Y_
+    ///
+    /// (where 0 < _k_ < _Q_ is randomly generated)
+    fn encrypt(&self, m: &Point) -> PointCiphertext {
+        let q = modulus();
+        let k = random_bignum(&BigUint::one(), &q);
+
+        let c1 = Point::generator().scalar_mul(&k);
+        let c2 = m.add(&self.y.scalar_mul(&k));
+
+        PointCiphertext { c1, c2 }
+    }
+
+    /// Recover the message point as _m_ = _c_<sub>2</sub> - _x_ *
+    /// _c_<sub>1</sub>
+    fn decrypt(&self, c: &PointCiphertext) -> Point {
+        let shared_secret = c.c1.scalar_mul(&self.x);
+
+        c.c2.add(&shared_secret.neg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_identity_is_neutral() {
+        let g = Point::generator();
+
+        assert_eq!(g.add(&Point::identity()), g);
+    }
+
+    #[test]
+    fn test_point_negation() {
+        let g = Point::generator();
+
+        assert_eq!(g.add(&g.neg()), Point::identity());
+    }
+
+    #[test]
+    fn test_scalar_mul_matches_repeated_addition() {
+        let g = Point::generator();
+
+        let doubled = g.add(&g);
+        let scaled = g.scalar_mul(&BigUint::from(2u32));
+
+        assert_eq!(doubled, scaled);
+    }
+
+    #[test]
+    fn test_ec_elgamal_roundtrip() {
+        let ecelgamal = ECElGamal::generate();
+
+        // Encrypt the generator point itself, to avoid needing a full
+        // message-to-point encoding scheme
+        let m = Point::generator();
+
+        let encrypted = ecelgamal.encrypt(&m);
+        let decrypted = ecelgamal.decrypt(&encrypted);
+
+        assert_eq!(m, decrypted);
+    }
+}
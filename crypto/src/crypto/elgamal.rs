@@ -1,6 +1,8 @@
 //! Implementation of the Elgamal algorithm
 
 use super::*;
+use num_traits::One;
+use secret::Secret;
 
 pub struct ElGamal {
     /// Public prime
@@ -9,8 +11,48 @@ pub struct ElGamal {
     pub g: BigUint,
     /// Public value _g_<sup>_x_</sup> mod _p_
     pub y: BigUint,
-    /// Secret exponent
-    pub x: BigUint,
+    /// Secret exponent, wiped from memory on drop
+    pub x: Secret,
+}
+
+impl ElGamal {
+    /// Generate a fresh ElGamal key pair over a random `bits`-bit prime
+    /// field
+    ///
+    /// Searches for a safe prime _p_ = 2_q_ + 1 (with _q_ itself prime) so
+    /// that the quadratic residues mod _p_ form a subgroup of large prime
+    /// order _q_, derives a generator `g` of that subgroup as the square of
+    /// a random base, then picks a random secret exponent `x` in
+    /// `[1, q - 1]`.
+    pub fn generate(bits: usize) -> Self {
+        let (p, q) = loop {
+            let q = keygen::gen_prime(bits - 1);
+            let candidate = &q * BigUint::from(2u32) + BigUint::one();
+
+            if keygen::is_prime(&candidate, keygen::MR_ROUNDS) {
+                break (candidate, q);
+            }
+        };
+
+        let g = loop {
+            let h = random_bignum(&BigUint::from(2u32), &(&p - BigUint::from(2u32)));
+            let candidate = h.modpow(&BigUint::from(2u32), &p);
+
+            if candidate != BigUint::one() {
+                break candidate;
+            }
+        };
+
+        let x = random_bignum(&BigUint::one(), &q);
+        let y = g.modpow(&x, &p);
+
+        ElGamal {
+            p,
+            g,
+            y,
+            x: Secret::from(x),
+        }
+    }
 }
 
 impl Encrypt for ElGamal {
@@ -23,10 +65,13 @@ impl Encrypt for ElGamal {
     /// (where 0 < _k_ < _p_ - 1 is randomly generated and _s_ =  _y_<sup>_k_</sup> is the _shared secret_)
     fn encrypt(&self, m: &BigUint) -> Ciphertext {
         // Generate a random k between 1 and p-1
-        let k = random_bignum(&BigUint::from(1u32), &(&self.p - BigUint::from(1u32)));
-        let s = self.y.modpow(&k, &self.p); // shared secret s
+        let k = Secret::from(random_bignum(
+            &BigUint::from(1u32),
+            &(&self.p - BigUint::from(1u32)),
+        ));
+        let s = self.y.modpow(&k.expose_secret(), &self.p); // shared secret s
 
-        let c1 = self.g.modpow(&k, &self.p);
+        let c1 = self.g.modpow(&k.expose_secret(), &self.p);
         let c2 = (m * s) % &self.p;
 
         Ciphertext::Pair(c1, c2)
@@ -43,7 +88,8 @@ impl Encrypt for ElGamal {
         match c {
             Ciphertext::Pair(c1, c2) => {
                 // Compute the inverse of the shared secret s
-                let s_inv = c1.modpow(&(&self.p - BigUint::from(1u32) - &self.x), &self.p);
+                let s_inv =
+                    c1.modpow(&(&self.p - BigUint::from(1u32) - self.x.expose_secret()), &self.p);
                 let m = (c2 * s_inv) % &self.p;
 
                 m
@@ -53,6 +99,10 @@ impl Encrypt for ElGamal {
     }
 }
 
+/// Dropping an `ElGamal` key wipes its secret exponent `x` from memory,
+/// since it is a [`Secret`]
+impl zeroize::ZeroizeOnDrop for ElGamal {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,7 +115,7 @@ mod tests {
             p: BigUint::from(2357u32),
             g: BigUint::from(2u32),
             y: BigUint::from(1185u32),
-            x: BigUint::from(1751u32),
+            x: Secret::from(BigUint::from(1751u32)),
         };
 
         let m = BigUint::from(2035u32);
@@ -82,7 +132,9 @@ mod tests {
             p: bignum(b"21184795224212536964062883050432832896219180043306745749507173456191006787311146854668821513315952228690166108340246881055280083954021140230360109139210549183430005605616829049480465189085545832479727332745387886538641769815794752311817699632294459913736902844395790405051970352731077204037998783513130589208851997845158638472072468616025046402553224295502860056712883342790113689935316985246818793713930252667398829988405042143167096182757216513627895445171115572143858787433983678864090060986677504505167265543059226905114937436266049720413372897671084091167754147649933819526873415745134475534382738086734552688143"),
             g: bignum(b"5"),
             y: bignum(b"19807665444265041657990177107385033349747839926670669671385346779887167874349638280822801372810919990395863498154790640244438754036977740163782735915326218215020732086291478236345235716255836603410188555847043334823639271225009503959675200461464217135020809968239213787524669134970143391638737520877381736741234294852676687654189217772756223053069824285066683179699040712719155241983138335681604882270920880707772542759415275782192139967872091314569380301748781104585325131212122744030265022966524566056609327022825696274689322286018817050110030541738742416397112862361974086701732959305990984850610177647733174357595"),
-            x: bignum(b"1270742310900726690413026462488924015958858380202122408190957963265926396562890535592476096127516928825")
+            x: Secret::from(bignum(
+                b"1270742310900726690413026462488924015958858380202122408190957963265926396562890535592476096127516928825",
+            )),
         };
 
         let m = bignum(b"1482726341215123");
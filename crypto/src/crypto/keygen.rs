@@ -0,0 +1,84 @@
+//! Probabilistic prime generation for on-the-fly key generation
+
+use crate::utils::random_bignum;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::One;
+
+/// Default number of Miller-Rabin rounds, giving a false-positive
+/// probability of at most 4<sup>-40</sup> for a composite candidate
+pub const MR_ROUNDS: u32 = 40;
+
+/// Test `n` for primality using `rounds` rounds of the Miller-Rabin test
+///
+/// Writes _n_ - 1 = 2<sup>_s_</sup> * _d_ with _d_ odd, then for each of
+/// `rounds` random bases _a_ in [2, _n_ - 2] computes _x_ = _a_<sup>_d_</sup>
+/// mod _n_: if _x_ == 1 or _x_ == _n_ - 1 the base is inconclusive,
+/// otherwise _x_ is squared up to _s_ - 1 times looking for _n_ - 1; if it
+/// is never found, `n` is composite.
+pub fn is_prime(n: &BigUint, rounds: u32) -> bool {
+    let two = BigUint::from(2u32);
+    let three = BigUint::from(3u32);
+
+    if *n < two {
+        return false;
+    }
+    if *n == two || *n == three {
+        return true;
+    }
+    if n.is_even() {
+        return false;
+    }
+
+    let n_minus_one = n - BigUint::one();
+    let mut d = n_minus_one.clone();
+    let mut s = 0u32;
+
+    while d.is_even() {
+        d >>= 1;
+        s += 1;
+    }
+
+    'rounds: for _ in 0..rounds {
+        let a = random_bignum(&two, &(n - BigUint::one()));
+        let mut x = a.modpow(&d, n);
+
+        if x == BigUint::one() || x == n_minus_one {
+            continue;
+        }
+
+        for _ in 0..s - 1 {
+            x = x.modpow(&two, n);
+
+            if x == n_minus_one {
+                continue 'rounds;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+/// Generate a random probable prime of exactly `bits` bits
+///
+/// Samples a random odd `bits`-bit `BigUint` and tests it with [`is_prime`]
+/// using [`MR_ROUNDS`] rounds, moving on to the next odd candidate until one
+/// passes.
+pub fn gen_prime(bits: usize) -> BigUint {
+    let lower = BigUint::one() << (bits - 1);
+    let upper = BigUint::one() << bits;
+
+    let mut candidate = random_bignum(&lower, &upper) | BigUint::one();
+
+    while !is_prime(&candidate, MR_ROUNDS) {
+        candidate += BigUint::from(2u32);
+
+        if candidate >= upper {
+            candidate = random_bignum(&lower, &upper) | BigUint::one();
+        }
+    }
+
+    candidate
+}
@@ -1,14 +1,275 @@
 //! Implementation of the RSA algorithm
 
 use super::*;
+use crate::utils::mod_inverse;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::One;
+use rand::Rng;
+use secret::Secret;
+use sha2::{Digest, Sha256};
+
+/// ASN.1 DER prefix identifying a SHA-256 digest inside a PKCS#1
+/// DigestInfo structure (RFC 8017, appendix B.1)
+const SHA256_DIGEST_INFO_PREFIX: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+    0x05, 0x00, 0x04, 0x20,
+];
+
+/// Byte length of the modulus `n`, i.e. `k` in RFC 8017
+fn modulus_len(n: &BigUint) -> usize {
+    (n.bits() as usize).div_ceil(8)
+}
+
+/// Left-pad `bytes` with zeros up to exactly `len` bytes
+fn left_pad(bytes: Vec<u8>, len: usize) -> Vec<u8> {
+    if bytes.len() >= len {
+        return bytes;
+    }
+
+    let mut padded = vec![0u8; len - bytes.len()];
+    padded.extend_from_slice(&bytes);
+    padded
+}
+
+/// Fill `len` bytes with random non-zero values, as required by the `PS`
+/// padding string in EME-PKCS1-v1_5
+fn random_nonzero_bytes(len: usize) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    let mut bytes = Vec::with_capacity(len);
+
+    while bytes.len() < len {
+        let b: u8 = rng.gen();
+
+        if b != 0 {
+            bytes.push(b);
+        }
+    }
+
+    bytes
+}
+
+/// Apply EME-PKCS1-v1_5 padding: `0x00 || 0x02 || PS || 0x00 || msg`, where
+/// `PS` is at least 8 random non-zero bytes filling the modulus byte length
+/// `k`
+fn eme_pkcs1_pad(msg: &[u8], k: usize) -> Vec<u8> {
+    assert!(
+        msg.len() + 11 <= k,
+        "message too long for this modulus size"
+    );
+
+    let ps = random_nonzero_bytes(k - msg.len() - 3);
+
+    let mut em = Vec::with_capacity(k);
+    em.push(0x00);
+    em.push(0x02);
+    em.extend_from_slice(&ps);
+    em.push(0x00);
+    em.extend_from_slice(msg);
+
+    em
+}
+
+/// Undo [`eme_pkcs1_pad`], validating the `0x00 0x02` prefix and the `PS`
+/// separator
+fn eme_pkcs1_unpad(em: &[u8]) -> Vec<u8> {
+    assert!(
+        em.len() >= 11 && em[0] == 0x00 && em[1] == 0x02,
+        "invalid PKCS#1 v1.5 encryption padding"
+    );
+
+    let separator = em[2..]
+        .iter()
+        .position(|&b| b == 0x00)
+        .expect("missing PKCS#1 v1.5 padding separator");
+
+    em[2 + separator + 1..].to_vec()
+}
+
+/// Apply EMSA-PKCS1-v1_5 encoding to the SHA-256 digest of `msg`:
+/// `0x00 || 0x01 || PS || 0x00 || DigestInfo`, where `PS` is `0xff` bytes
+/// filling the modulus byte length `k`
+fn emsa_pkcs1_encode(msg: &[u8], k: usize) -> Vec<u8> {
+    let mut digest_info = SHA256_DIGEST_INFO_PREFIX.to_vec();
+    digest_info.extend_from_slice(&Sha256::digest(msg));
+
+    assert!(
+        digest_info.len() + 11 <= k,
+        "digest too long for this modulus size"
+    );
+
+    let mut em = Vec::with_capacity(k);
+    em.push(0x00);
+    em.push(0x01);
+    em.extend(std::iter::repeat_n(0xffu8, k - digest_info.len() - 3));
+    em.push(0x00);
+    em.extend_from_slice(&digest_info);
+
+    em
+}
 
 pub struct RSA {
     /// Public modulus
     pub n: BigUint,
     /// Public exponent
     pub e: BigUint,
-    /// Secret exponent
-    pub d: BigUint,
+    /// Secret exponent, wiped from memory on drop
+    pub d: Secret,
+    /// Optional precomputed CRT parameters enabling the faster private-key
+    /// path in [`Encrypt::decrypt`] and [`Sign::sign`]
+    pub crt: Option<CrtParams>,
+}
+
+/// Precomputed Chinese Remainder Theorem parameters for the two prime
+/// factors of an RSA modulus
+///
+/// `p` and `q` reconstruct the secret exponent `d` outright, so every field
+/// here is as sensitive as `d` itself and is wiped from memory on drop.
+pub struct CrtParams {
+    /// First prime factor of `n`
+    pub p: Secret,
+    /// Second prime factor of `n`
+    pub q: Secret,
+    /// _d_ mod (_p_ - 1)
+    pub dp: Secret,
+    /// _d_ mod (_q_ - 1)
+    pub dq: Secret,
+    /// _q_<sup>-1</sup> mod _p_
+    pub qinv: Secret,
+}
+
+impl CrtParams {
+    /// Precompute `dp`, `dq` and `qinv` from the prime factors `p`, `q` and
+    /// the secret exponent `d`
+    pub fn new(p: &BigUint, q: &BigUint, d: &BigUint) -> Self {
+        let dp = d % (p - BigUint::one());
+        let dq = d % (q - BigUint::one());
+        let qinv = mod_inverse(q, p);
+
+        CrtParams {
+            p: Secret::from(p.clone()),
+            q: Secret::from(q.clone()),
+            dp: Secret::from(dp),
+            dq: Secret::from(dq),
+            qinv: Secret::from(qinv),
+        }
+    }
+
+    /// Apply the private-key operation _`c`_<sup>_d_</sup> mod _n_ via CRT
+    ///
+    /// Computes _m_<sub>1</sub> = _`c`_<sup>_dp_</sup> mod _p_,
+    /// _m_<sub>2</sub> = _`c`_<sup>_dq_</sup> mod _q_,
+    /// _h_ = (_qinv_ * (_m_<sub>1</sub> - _m_<sub>2</sub>)) mod _p_, then
+    /// recombines _m_ = _m_<sub>2</sub> + _h_ * _q_.
+    pub fn apply(&self, c: &BigUint) -> BigUint {
+        let p = self.p.expose_secret();
+        let q = self.q.expose_secret();
+
+        let m1 = c.modpow(&self.dp.expose_secret(), &p);
+        let m2 = c.modpow(&self.dq.expose_secret(), &q);
+
+        let p_signed = BigInt::from(p.clone());
+        let diff = ((BigInt::from(m1) - BigInt::from(m2.clone())) % &p_signed + &p_signed)
+            % &p_signed;
+        let diff = diff
+            .to_biguint()
+            .expect("remainder modulo a positive modulus is never negative");
+
+        let h = (self.qinv.expose_secret() * diff) % &p;
+
+        m2 + h * &q
+    }
+}
+
+/// Dropping `CrtParams` wipes `p`, `q`, `dp`, `dq` and `qinv` from memory,
+/// since every field is a [`Secret`]
+impl zeroize::ZeroizeOnDrop for CrtParams {}
+
+impl RSA {
+    /// Generate a fresh RSA key pair with a modulus of approximately
+    /// `bits` bits
+    ///
+    /// Picks two `bits / 2`-bit primes _p_ and _q_ via [`keygen::gen_prime`]
+    /// and delegates to [`RSA::from_primes`] with the common public
+    /// exponent 65537.
+    pub fn generate(bits: usize) -> Self {
+        let p = keygen::gen_prime(bits / 2);
+        let q = keygen::gen_prime(bits / 2);
+
+        Self::from_primes(p, q, BigUint::from(65537u32))
+    }
+
+    /// Build an RSA key from its prime factors `p`, `q` and a public
+    /// exponent `e`
+    ///
+    /// Sets _n_ = _p_ * _q_, computes the secret exponent as
+    /// _d_ = _e_<sup>-1</sup> mod lcm(_p_ - 1, _q_ - 1), and precomputes the
+    /// [`CrtParams`] used by the fast private-key path.
+    pub fn from_primes(p: BigUint, q: BigUint, e: BigUint) -> Self {
+        let n = &p * &q;
+
+        let p_minus_one = &p - BigUint::one();
+        let q_minus_one = &q - BigUint::one();
+        let lambda = (&p_minus_one * &q_minus_one) / p_minus_one.gcd(&q_minus_one);
+
+        let d = mod_inverse(&e, &lambda);
+        let crt = CrtParams::new(&p, &q, &d);
+
+        RSA {
+            n,
+            e,
+            d: Secret::from(d),
+            crt: Some(crt),
+        }
+    }
+
+    /// Encrypt an arbitrary byte message, applying EME-PKCS1-v1_5 padding
+    /// before the raw RSA operation
+    pub fn encrypt_bytes(&self, msg: &[u8]) -> Ciphertext {
+        let k = modulus_len(&self.n);
+        let padded = eme_pkcs1_pad(msg, k);
+
+        self.encrypt(&BigUint::from_bytes_be(&padded))
+    }
+
+    /// Decrypt a ciphertext produced by [`RSA::encrypt_bytes`], stripping
+    /// the EME-PKCS1-v1_5 padding from the recovered message
+    pub fn decrypt_bytes(&self, c: &Ciphertext) -> Vec<u8> {
+        let k = modulus_len(&self.n);
+        let padded = left_pad(self.decrypt(c).to_bytes_be(), k);
+
+        eme_pkcs1_unpad(&padded)
+    }
+
+    /// Sign an arbitrary byte message, applying EMSA-PKCS1-v1_5 encoding of
+    /// its SHA-256 digest before the raw RSA operation
+    pub fn sign_bytes(&self, msg: &[u8]) -> Signature {
+        let k = modulus_len(&self.n);
+        let em = BigUint::from_bytes_be(&emsa_pkcs1_encode(msg, k));
+
+        let s = match &self.crt {
+            Some(crt) => crt.apply(&em),
+            None => em.modpow(&self.d.expose_secret(), &self.n),
+        };
+
+        Signature::Single(s)
+    }
+
+    /// Verify a signature produced by [`RSA::sign_bytes`] by recomputing
+    /// the expected EMSA-PKCS1-v1_5 encoding and comparing it to the
+    /// recovered one
+    pub fn verify_bytes(&self, msg: &[u8], sig: &Signature) -> bool {
+        let k = modulus_len(&self.n);
+
+        match sig {
+            Signature::Single(s) => {
+                let em = left_pad(s.modpow(&self.e, &self.n).to_bytes_be(), k);
+
+                em == emsa_pkcs1_encode(msg, k)
+            }
+            _ => panic!("Not a single value"),
+        }
+    }
 }
 
 /// Use the RSA algorithm to encrypt and decrypt messages
@@ -18,10 +279,15 @@ impl Encrypt for RSA {
         Ciphertext::Single(m.modpow(&self.e, &self.n))
     }
 
-    /// Compute _`c`_<sup>_d_</sup> mod _n_
+    /// Compute _`c`_<sup>_d_</sup> mod _n_, using the CRT fast path when
+    /// [`CrtParams`] are available and falling back to the full-modulus
+    /// exponentiation otherwise
     fn decrypt(&self, c: &Ciphertext) -> BigUint {
         match c {
-            Ciphertext::Single(c) => c.modpow(&self.d, &self.n),
+            Ciphertext::Single(c) => match &self.crt {
+                Some(crt) => crt.apply(c),
+                None => c.modpow(&self.d.expose_secret(), &self.n),
+            },
             _ => panic!("Not a single value"),
         }
     }
@@ -37,9 +303,17 @@ impl Sign for RSA {
         BigUint::parse_bytes(h.as_bytes(), 16).expect("Cannot convert bytes to BigUint")
     }
 
-    /// Produce the signature hash(_`m`_)<sup>_d_</sup> mod _n_
+    /// Produce the signature hash(_`m`_)<sup>_d_</sup> mod _n_, using the
+    /// CRT fast path when [`CrtParams`] are available
     fn sign(&self, m: &BigUint) -> Signature {
-        Signature::Single(Self::hash(m).modpow(&self.d, &self.n))
+        let h = Self::hash(m);
+
+        let s = match &self.crt {
+            Some(crt) => crt.apply(&h),
+            None => h.modpow(&self.d.expose_secret(), &self.n),
+        };
+
+        Signature::Single(s)
     }
 
     /// Compute _`sig`_<sup>_e_</sup> mod _n_ and compare to hash(_`m`_)
@@ -51,6 +325,11 @@ impl Sign for RSA {
     }
 }
 
+/// Dropping an `RSA` key wipes its secret exponent `d` from memory, and its
+/// `crt` parameters if present, since every sensitive field of both is a
+/// [`Secret`]
+impl zeroize::ZeroizeOnDrop for RSA {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,7 +341,8 @@ mod tests {
         let rsa = RSA {
             n: BigUint::from(2357u32) * BigUint::from(2551u32),
             e: BigUint::from(3674911u32),
-            d: BigUint::from(422191u32),
+            d: Secret::from(BigUint::from(422191u32)),
+            crt: None,
         };
 
         let m = BigUint::from(5234673u32);
@@ -78,7 +358,8 @@ mod tests {
         let rsa = RSA {
             n: bignum(b"24285567456616572535053163704040517696339053520634523513959490724007229796719740152317361083535903559526887151910583738749192724073752114685883652689425560188238375621459958617205457390994531647966048552252431253837715142607154249583275263403961793022725225708928576824094708202567623969946919484541872521257547449677583916437272177792287910013177936025088702170345854171069059816126279489604018885163082286699535072424228488832207776143066543758831629156184365560217187829162278060910799742497812823133120175704776511913669284170673753127829411572441993508065373965371003598177072369409326086217971424873326320403767"),
             e: bignum(b"65537"),
-            d: bignum(b"4246648504704608408253494301666300453179815269946314897996181755300408219332716208333566657267214776266507877395902919664704649554987247422070382529270746597452000925003145181396379780899298605149624126963590981719947747597204444820854395511076218747279110832420182345913382608319345876307913045956480447588505904098748546621165568873421092788829173712507771720454061237012649434229751644062202815204041718422716997993742509002869211664920269003099439680742846774758669152760830688242344700050431906885674769163678723468025888796520771344968669620518651547899167951600305545613239608787193640741107853748457567524673")
+            d: Secret::from(bignum(b"4246648504704608408253494301666300453179815269946314897996181755300408219332716208333566657267214776266507877395902919664704649554987247422070382529270746597452000925003145181396379780899298605149624126963590981719947747597204444820854395511076218747279110832420182345913382608319345876307913045956480447588505904098748546621165568873421092788829173712507771720454061237012649434229751644062202815204041718422716997993742509002869211664920269003099439680742846774758669152760830688242344700050431906885674769163678723468025888796520771344968669620518651547899167951600305545613239608787193640741107853748457567524673")),
+            crt: None,
         };
 
         let m = bignum(b"1482726341215123");
@@ -88,4 +369,84 @@ mod tests {
 
         assert_eq!(m, rsa_decoded);
     }
+
+    #[test]
+    fn test_rsa_crt() {
+        // Numbers from the HAC book
+        let rsa = RSA::from_primes(
+            BigUint::from(2357u32),
+            BigUint::from(2551u32),
+            BigUint::from(3674911u32),
+        );
+
+        let m = BigUint::from(5234673u32);
+
+        let rsa_encoded = rsa.encrypt(&m);
+        let rsa_decoded = rsa.decrypt(&rsa_encoded);
+
+        assert_eq!(m, rsa_decoded);
+    }
+
+    #[test]
+    fn test_rsa_encrypt_bytes() {
+        let rsa = RSA::from_primes(
+            bignum(
+                b"16157387885063800092468972531095442600227637936690303362357377535130907802167",
+            ),
+            bignum(
+                b"68374361576449959379811878238702970795767227995234058958640265755013581201943",
+            ),
+            BigUint::from(65537u32),
+        );
+
+        let msg = b"attack at dawn";
+
+        let encrypted = rsa.encrypt_bytes(msg);
+        let decrypted = rsa.decrypt_bytes(&encrypted);
+
+        assert_eq!(msg.to_vec(), decrypted);
+    }
+
+    #[test]
+    fn test_rsa_encrypt_bytes_is_randomized() {
+        let rsa = RSA::from_primes(
+            bignum(
+                b"16157387885063800092468972531095442600227637936690303362357377535130907802167",
+            ),
+            bignum(
+                b"68374361576449959379811878238702970795767227995234058958640265755013581201943",
+            ),
+            BigUint::from(65537u32),
+        );
+
+        let msg = b"attack at dawn";
+
+        let c1 = rsa.encrypt_bytes(msg);
+        let c2 = rsa.encrypt_bytes(msg);
+
+        match (c1, c2) {
+            (Ciphertext::Single(c1), Ciphertext::Single(c2)) => assert_ne!(c1, c2),
+            _ => panic!("Not a single value"),
+        }
+    }
+
+    #[test]
+    fn test_rsa_sign_bytes() {
+        let rsa = RSA::from_primes(
+            bignum(
+                b"16157387885063800092468972531095442600227637936690303362357377535130907802167",
+            ),
+            bignum(
+                b"68374361576449959379811878238702970795767227995234058958640265755013581201943",
+            ),
+            BigUint::from(65537u32),
+        );
+
+        let msg = b"attack at dawn";
+
+        let sig = rsa.sign_bytes(msg);
+
+        assert!(rsa.verify_bytes(msg, &sig));
+        assert!(!rsa.verify_bytes(b"attack at dusk", &sig));
+    }
 }
@@ -0,0 +1,38 @@
+//! Wrapper that wipes secret `BigUint` material from memory when dropped
+
+use num_bigint::BigUint;
+use zeroize::Zeroize;
+
+/// A `BigUint` stored as its raw base-2<sup>32</sup> digits, which overwrites
+/// those digits with zeros when dropped
+///
+/// `BigUint` itself exposes no mutable access to its backing limbs, so there
+/// is no way to wipe one in place; instead `Secret` keeps the only long-lived
+/// copy of the digits as a plain `Vec<u32>` it owns outright; [`Zeroize`] on
+/// that `Vec` is what actually gets wiped on drop. Used for private
+/// exponents and transient per-signature nonces, so that downstream users
+/// handling real secrets get some defense-in-depth against them lingering in
+/// memory after the value goes out of scope.
+pub struct Secret(Vec<u32>);
+
+impl From<BigUint> for Secret {
+    fn from(value: BigUint) -> Self {
+        Secret(value.to_u32_digits())
+    }
+}
+
+impl Secret {
+    /// Reconstruct the wrapped value as a `BigUint`
+    ///
+    /// This copies the digits out of the `Secret`, so the result is *not*
+    /// wiped on drop; only the original `Secret` is.
+    pub fn expose_secret(&self) -> BigUint {
+        BigUint::new(self.0.clone())
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
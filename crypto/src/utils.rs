@@ -1,6 +1,7 @@
 //! Generic utility functions
 
-use num_bigint::{BigUint, RandBigInt};
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_traits::{One, Zero};
 
 /// Create a BigUint number from a string (interpreted in base 10)
 pub fn bignum(s: &[u8]) -> BigUint {
@@ -14,3 +15,29 @@ pub fn random_bignum(lower: &BigUint, upper: &BigUint) -> BigUint {
     // Note: upper bound is not inclusive
     rng.gen_biguint_range(lower, upper)
 }
+
+/// Compute the modular inverse of `a` modulo `m` via the extended Euclidean
+/// algorithm, i.e. the `x` in `[0, m)` such that _`a`_ * _x_ ≡ 1 (mod _m_)
+pub fn mod_inverse(a: &BigUint, m: &BigUint) -> BigUint {
+    let modulus = BigInt::from(m.clone());
+    let (mut old_r, mut r) = (BigInt::from(a.clone()), modulus.clone());
+    let (mut old_s, mut s) = (BigInt::one(), BigInt::zero());
+
+    while !r.is_zero() {
+        let quotient = &old_r / &r;
+
+        let new_r = &old_r - &quotient * &r;
+        old_r = r;
+        r = new_r;
+
+        let new_s = &old_s - &quotient * &s;
+        old_s = s;
+        s = new_s;
+    }
+
+    let inverse = ((old_s % &modulus) + &modulus) % &modulus;
+
+    inverse
+        .to_biguint()
+        .expect("remainder modulo a positive modulus is never negative")
+}